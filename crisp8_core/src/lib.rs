@@ -1,18 +1,30 @@
 use rand::random;
 
+// lo-res (original CHIP-8) display dimensions
 pub const SCREEN_WIDTH: usize = 64;
 pub const SCREEN_HEIGHT: usize = 32;
+// hi-res (SUPER-CHIP) display dimensions
+pub const HIRES_SCREEN_WIDTH: usize = 128;
+pub const HIRES_SCREEN_HEIGHT: usize = 64;
 
 const RAM_SIZE: usize = 4096;
 const NUM_REGS: usize = 16;
 const STACK_SIZE: usize = 16;
 const NUM_KEYS: usize = 16;
+const NUM_FLAGS: usize = 8;
 
 // originally the chip-8 interpreter was located in ram 0x000 - 0x1ff, and expected programs to load right after
 const START_ADDR: u16 = 0x200;
 const FONT_ADDR: u16 = 0x050;
 
 const FONTSET_SIZE: usize = 80;
+// the SUPER-CHIP big font is stored right after the regular font
+const BIGFONT_ADDR: u16 = FONT_ADDR + FONTSET_SIZE as u16;
+const BIGFONTSET_SIZE: usize = 160;
+
+// identifies the byte layout below as a CRISP-8 snapshot, and which version of it
+const SNAPSHOT_MAGIC: [u8; 3] = *b"C8S";
+const SNAPSHOT_VERSION: u8 = 2;
 
 const FONTSET: [u8; FONTSET_SIZE] = [
 0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
@@ -33,12 +45,104 @@ const FONTSET: [u8; FONTSET_SIZE] = [
 0xF0, 0x80, 0xF0, 0x80, 0x80 // F
 ];
 
+// the SUPER-CHIP big font: 10 bytes per glyph instead of 5, for 8x10 hi-res digit sprites
+const BIGFONTSET: [u8; BIGFONTSET_SIZE] = [
+0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+0x3E, 0x7C, 0xC0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+0x3C, 0x7E, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+0xFC, 0xFE, 0xC3, 0xC3, 0xFC, 0xFE, 0xC3, 0xC3, 0xFE, 0xFC, // B
+0x3C, 0x7E, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0x7E, 0x3C, // C
+0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0 // F
+];
+
+// the various CHIP-8 interpreters in the wild disagree on a handful of opcode behaviors;
+// a ROM only runs correctly under the combination its author targeted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks
+{
+	// 8XY6/8XYE: copy VY into VX before shifting, instead of shifting VX in place
+	pub shift_uses_vy: bool,
+	// BNNN: treat the second nibble as X and jump to XNN + VX, instead of NNN + V0
+	pub jump_offset_uses_vx: bool,
+	// FX55/FX65: leave I advanced to I + X + 1 after the loop, instead of restoring it
+	pub load_store_increments_i: bool,
+	// FX1E: set VF when I overflows past 0x0fff
+	pub index_add_sets_vf: bool,
+	// DXYN: wrap pixels that run off the edge of the screen, instead of clipping them
+	pub sprite_wraps: bool,
+	// 8XY1/8XY2/8XY3: reset VF to 0 as a side effect of the logic ops
+	pub vf_reset_on_logic: bool
+}
+
+impl Quirks
+{
+	// the original COSMAC VIP interpreter CHIP-8 was designed around
+	pub fn cosmac_vip() -> Self
+	{
+		Self {
+			shift_uses_vy: true,
+			jump_offset_uses_vx: false,
+			load_store_increments_i: true,
+			index_add_sets_vf: false,
+			sprite_wraps: false,
+			vf_reset_on_logic: true
+		}
+	}
+
+	// the HP48 calculator interpreter most SUPER-CHIP era ROMs were written against
+	pub fn chip48() -> Self
+	{
+		Self {
+			shift_uses_vy: false,
+			jump_offset_uses_vx: true,
+			load_store_increments_i: false,
+			index_add_sets_vf: false,
+			sprite_wraps: false,
+			vf_reset_on_logic: false
+		}
+	}
+
+	// what most modern interpreters settled on, and what this emulator has always done
+	pub fn modern() -> Self
+	{
+		Self::default()
+	}
+}
+
+impl Default for Quirks
+{
+	fn default() -> Self
+	{
+		Self {
+			shift_uses_vy: false,
+			jump_offset_uses_vx: false,
+			load_store_increments_i: false,
+			index_add_sets_vf: true,
+			sprite_wraps: false,
+			vf_reset_on_logic: false
+		}
+	}
+}
+
 pub struct Emu
 {
 	// program counter, stores info about which instruction to execute next
 	pc: u16,
 	ram: [u8; RAM_SIZE],
-	screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+	// sized for the active resolution: SCREEN_WIDTH*SCREEN_HEIGHT in lo-res, HIRES_SCREEN_WIDTH*HIRES_SCREEN_HEIGHT in hi-res
+	screen: Vec<bool>,
+	// whether the display is currently running in SUPER-CHIP's 128x64 hi-res mode
+	hires: bool,
 	keys: [bool; NUM_KEYS],
 	// registers, numbered V0 to VF
 	// VF is used as a flag register
@@ -52,37 +156,59 @@ pub struct Emu
 	// delay timer register, counts down every cycle and performs an action when it hits 0
 	dt: u8,
 	// sound timer register, counts down every cycle, emits a noise when it hits 0
-	st: u8
+	st: u8,
+	// which compatibility behaviors to use for opcodes that differ between interpreters
+	quirks: Quirks,
+	// set whenever the screen buffer changes, so the frontend can skip redrawing unchanged frames
+	draw_flag: bool,
+	// FX75/FX85 persistent flag registers (HP48 calculator flags on real SUPER-CHIP hardware); survive reset()
+	flags: [u8; NUM_FLAGS]
 }
 
 impl Emu
 {
 	pub fn new() -> Self
+	{
+		Self::new_with_quirks(Quirks::default())
+	}
+
+	pub fn new_with_quirks(quirks: Quirks) -> Self
 	{
 		let mut new_emu = Self {
 			pc: START_ADDR,
 			ram: [0; RAM_SIZE],
-			screen: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
+			screen: vec![false; SCREEN_WIDTH * SCREEN_HEIGHT],
+			hires: false,
 			keys: [false; NUM_KEYS],
 			v_reg: [0; NUM_REGS],
 			i_reg: 0,
 			sp: 0,
 			stack: [0; STACK_SIZE],
 			dt: 0,
-			st: 0
+			st: 0,
+			quirks,
+			draw_flag: false,
+			flags: [0; NUM_FLAGS]
 		};
 
 		// the fonts can be stored anywhere before 0x200, but putting it between 0x050 and 0x09f has become a popular convention in emulators
 		new_emu.ram[(FONT_ADDR as usize)..((FONT_ADDR as usize) + FONTSET_SIZE)].copy_from_slice(&FONTSET);
+		new_emu.ram[(BIGFONT_ADDR as usize)..((BIGFONT_ADDR as usize) + BIGFONTSET_SIZE)].copy_from_slice(&BIGFONTSET);
 
 		new_emu
 	}
 
+	pub fn set_quirks(&mut self, quirks: Quirks)
+	{
+		self.quirks = quirks;
+	}
+
 	pub fn reset(&mut self)
 	{
 		self.pc = START_ADDR;
 		self.ram = [0; RAM_SIZE];
-		self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+		self.screen = vec![false; SCREEN_WIDTH * SCREEN_HEIGHT];
+		self.hires = false;
 		self.keys = [false; NUM_KEYS];
 		self.v_reg = [0; NUM_REGS];
 		self.i_reg = 0;
@@ -90,7 +216,11 @@ impl Emu
 		self.stack = [0; STACK_SIZE];
 		self.dt = 0;
 		self.st = 0;
+		self.draw_flag = true;
+		// quirks are a configuration choice, not machine state, so reset() leaves them alone;
+		// flags are non-volatile on real SUPER-CHIP hardware, so reset() leaves them alone too
 		self.ram[(FONT_ADDR as usize)..((FONT_ADDR as usize) + FONTSET_SIZE)].copy_from_slice(&FONTSET);
+		self.ram[(BIGFONT_ADDR as usize)..((BIGFONT_ADDR as usize) + BIGFONTSET_SIZE)].copy_from_slice(&BIGFONTSET);
 	}
 
 	fn push(&mut self, val: u16)
@@ -113,6 +243,13 @@ impl Emu
 		self.execute(op);
 	}
 
+	// runs exactly one instruction; a thin alias over tick() so debuggers don't need to
+	// know that ticks and instructions happen to be the same thing
+	pub fn step(&mut self)
+	{
+		self.tick();
+	}
+
 	// unlike the regular ticks which operates once every cpu cycle, the timers are modified once every frame, thus needing a seperate function
 	pub fn tick_timers(&mut self)
 	{
@@ -124,14 +261,16 @@ impl Emu
 		if self.st > 0
 		{
 			self.st -= 1;
-			if self.st == 0
-			{
-				// sound here
-				println!("Boop!");
-			}
 		}
 	}
 
+	// whether a tone should currently be playing; the core has no audio code of its own,
+	// so it just reports the sound timer state and leaves producing the tone to the frontend
+	pub fn is_sound_active(&self) -> bool
+	{
+		self.st > 0
+	}
+
 	fn fetch(&mut self) -> u16
 	{
 		let higher_byte = self.ram[self.pc as usize] as u16;
@@ -153,7 +292,34 @@ impl Emu
 			// opcode 0000: NOP
 			(0x0, 0x0, 0x0, 0x0) => return,
 			// opcode 00e0: CLS
-			(0x0, 0x0, 0xe, 0x0) => { self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT]; },
+			(0x0, 0x0, 0xe, 0x0) => {
+				self.screen = vec![false; self.screen.len()];
+				self.draw_flag = true;
+			},
+			// opcode 00cn: SUPER-CHIP - scroll the display down N rows
+			(0x0, 0x0, 0xc, _) => {
+				self.scroll_down(digit4 as usize);
+			},
+			// opcode 00fb: SUPER-CHIP - scroll the display right 4 columns
+			(0x0, 0x0, 0xf, 0xb) => {
+				self.scroll_right();
+			},
+			// opcode 00fc: SUPER-CHIP - scroll the display left 4 columns
+			(0x0, 0x0, 0xf, 0xc) => {
+				self.scroll_left();
+			},
+			// opcode 00fe: SUPER-CHIP - switch back to 64x32 lo-res mode
+			(0x0, 0x0, 0xf, 0xe) => {
+				self.hires = false;
+				self.screen = vec![false; SCREEN_WIDTH * SCREEN_HEIGHT];
+				self.draw_flag = true;
+			},
+			// opcode 00ff: SUPER-CHIP - switch to 128x64 hi-res mode
+			(0x0, 0x0, 0xf, 0xf) => {
+				self.hires = true;
+				self.screen = vec![false; HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT];
+				self.draw_flag = true;
+			},
 			// opcode 1NNN: JMP NNN
 			(0x1, _, _, _) => {
 				let nnn = op & 0x0fff;
@@ -230,18 +396,30 @@ impl Emu
 				let x = digit2;
 				let y = digit3;
 				self.v_reg[x as usize] |= self.v_reg[y as usize];
+				if self.quirks.vf_reset_on_logic
+				{
+					self.v_reg[0xf] = 0;
+				}
 			},
 			// opcode 8XY2: VX &= VY
 			(0x8, _, _, 0x2) => {
 				let x = digit2;
 				let y = digit3;
 				self.v_reg[x as usize] &= self.v_reg[y as usize];
+				if self.quirks.vf_reset_on_logic
+				{
+					self.v_reg[0xf] = 0;
+				}
 			},
 			// opcode 8XY3: VX ^= VY
 			(0x8, _, _, 0x3) => {
 				let x = digit2;
 				let y = digit3;
 				self.v_reg[x as usize] ^= self.v_reg[y as usize];
+				if self.quirks.vf_reset_on_logic
+				{
+					self.v_reg[0xf] = 0;
+				}
 			},
 			// opcode 8XY4: VX += VY
 			// note - unlike 7XNN this does set the carry flag in case of overflow
@@ -275,9 +453,14 @@ impl Emu
 				self.v_reg[0xf] = new_vf;
 			},
 			// opcode 8XY6: VX >>= 1, sets flag to the lost bit
-			// note - older implementations first set VX to VY, but newer implementations ignore the Y value completely
+			// note - older implementations first set VX to VY, but newer implementations ignore the Y value completely (quirks.shift_uses_vy)
 			(0x8, _, _, 0x6) => {
 				let x = digit2;
+				let y = digit3;
+				if self.quirks.shift_uses_vy
+				{
+					self.v_reg[x as usize] = self.v_reg[y as usize];
+				}
 				let lost_bit = self.v_reg[x as usize] & 1;
 				self.v_reg[x as usize] >>= 1;
 				self.v_reg[0xf] = lost_bit;
@@ -286,6 +469,11 @@ impl Emu
 			// note - same as 8XY6
 			(0x8, _, _, 0xe) => {
 				let x = digit2;
+				let y = digit3;
+				if self.quirks.shift_uses_vy
+				{
+					self.v_reg[x as usize] = self.v_reg[y as usize];
+				}
 				let lost_bit = (self.v_reg[x as usize] >> 7) & 1;
 				self.v_reg[x as usize] <<= 1;
 				self.v_reg[0xf] = lost_bit;
@@ -297,10 +485,18 @@ impl Emu
 			},
 			// opcode BNNN: JMP NNN + V0
 			// note - some newer implementations (likely unintentionally) treat the second digit as X, resulting in JMP XNN + VX
-			// this is not a common operation, so sticking with the older version should be fine
+			// (quirks.jump_offset_uses_vx)
 			(0xb, _, _, _) => {
 				let nnn = op & 0x0fff;
-				self.pc = self.v_reg[0x0] as u16 + nnn;
+				if self.quirks.jump_offset_uses_vx
+				{
+					let x = digit2;
+					self.pc = self.v_reg[x as usize] as u16 + nnn;
+				}
+				else
+				{
+					self.pc = self.v_reg[0x0] as u16 + nnn;
+				}
 			},
 			// opcode CXNN: rand() & NN
 			(0xc, _, _, _) => {
@@ -348,11 +544,15 @@ impl Emu
 			},
 			// opcode FX1E: I += VX, sets flag to 1 if I "overflows" from 0x0fff to 0x1000
 			// note - not all implementations set the flag, but there's no harm in doing it (and some games might rely on it)
+			// (quirks.index_add_sets_vf)
 			(0xf, _, 0x1, 0xe) => {
 				let x = digit2;
 				self.i_reg += self.v_reg[x as usize] as u16;
-				let new_vf = if self.i_reg >= 0x1000 { 1 } else { 0 };
-				self.v_reg[0xf] = new_vf;
+				if self.quirks.index_add_sets_vf
+				{
+					let new_vf = if self.i_reg >= 0x1000 { 1 } else { 0 };
+					self.v_reg[0xf] = new_vf;
+				}
 			},
 			// opcode FX0A: Blocks continuation of program until a key press is detected, then stores it into VX
 			// note - in case of multiple pressed keys, take the lowest indexed one
@@ -382,6 +582,13 @@ impl Emu
 				let c = c & 0x0f;
 				self.i_reg = FONT_ADDR + c * 5;
 			},
+			// opcode FX30: SUPER-CHIP - set I to the address of the 10-byte hi-res font glyph for digit VX
+			(0xf, _, 0x3, 0x0) => {
+				let x = digit2;
+				let c = self.v_reg[x as usize] as u16;
+				let c = c & 0x0f;
+				self.i_reg = BIGFONT_ADDR + c * 10;
+			},
 			// opcode FX33: puts 3 decimal digits of the number in VX at I, I + 1 and I + 2 respectively
 			(0xf, _, 0x3, 0x3) => {
 				let x = digit2;
@@ -394,13 +601,17 @@ impl Emu
 				self.ram[(self.i_reg + 2) as usize] = digit3;
 			},
 			// opcode FX55: store registers V0 - VX to memory, in incremental addresses starting from I
-			// note - originally I was modified in the process, but modern implementations leave it intact
+			// note - originally I was modified in the process, but modern implementations leave it intact (quirks.load_store_increments_i)
 			(0xf, _, 0x5, 0x5) => {
 				let x = digit2;
 				for i in 0..(x + 1) as u32
 				{
 					self.ram[(self.i_reg as u32 + i) as usize] = self.v_reg[i as usize];
 				}
+				if self.quirks.load_store_increments_i
+				{
+					self.i_reg += x + 1;
+				}
 			},
 			// opcode FX65: load values stored at I - I + X, and store them in registers V0 - VX
 			(0xf, _, 0x6, 0x5) => {
@@ -409,16 +620,88 @@ impl Emu
 				{
 					self.v_reg[i as usize] = self.ram[(self.i_reg as u32 + i) as usize];
 				}
+				if self.quirks.load_store_increments_i
+				{
+					self.i_reg += x + 1;
+				}
 			}
+			// opcode FX75: SUPER-CHIP - save V0 - VX to the persistent flag registers
+			// note - real SCHIP hardware only has 8 flag registers (RPL user flags), so X is clamped to 0-7
+			(0xf, _, 0x7, 0x5) => {
+				let x = (digit2 as usize).min(7);
+				self.flags[0..=x].copy_from_slice(&self.v_reg[0..=x]);
+			},
+			// opcode FX85: SUPER-CHIP - load V0 - VX from the persistent flag registers
+			// note - same 0-7 clamp as FX75
+			(0xf, _, 0x8, 0x5) => {
+				let x = (digit2 as usize).min(7);
+				self.v_reg[0..=x].copy_from_slice(&self.flags[0..=x]);
+			},
 			(_, _, _, _) => unimplemented!("Unimplemented opcode: {}", op),
 		}
 	}
 
+	// shifts the display down by n rows, zero-filling the rows vacated at the top
+	fn scroll_down(&mut self, n: usize)
+	{
+		let width = self.screen_width();
+		let height = self.screen_height();
+		for y in (0..height).rev()
+		{
+			for x in 0..width
+			{
+				self.screen[x + width * y] = y.checked_sub(n).is_some_and(|sy| self.screen[x + width * sy]);
+			}
+		}
+		self.draw_flag = true;
+	}
+
+	// shifts the display right by 4 columns, zero-filling the columns vacated on the left
+	fn scroll_right(&mut self)
+	{
+		let width = self.screen_width();
+		let height = self.screen_height();
+		for y in 0..height
+		{
+			for x in (0..width).rev()
+			{
+				self.screen[x + width * y] = x.checked_sub(4).is_some_and(|sx| self.screen[sx + width * y]);
+			}
+		}
+		self.draw_flag = true;
+	}
+
+	// shifts the display left by 4 columns, zero-filling the columns vacated on the right
+	fn scroll_left(&mut self)
+	{
+		let width = self.screen_width();
+		let height = self.screen_height();
+		for y in 0..height
+		{
+			for x in 0..width
+			{
+				let sx = x + 4;
+				self.screen[x + width * y] = if sx < width { self.screen[sx + width * y] } else { false };
+			}
+		}
+		self.draw_flag = true;
+	}
+
 	fn draw(&mut self, x: u16, y: u16, n: u16)
 	{
-		// the original x and y coordinates wrap around, however if the sprite goes off screen, it should be clipped instead of wrapped
-		let x_coord = self.v_reg[x as usize] as usize % SCREEN_WIDTH;
-		let y_coord = self.v_reg[y as usize] as usize % SCREEN_HEIGHT;
+		// in hi-res mode, n == 0 means a 16x16 sprite rather than the usual 8-wide, n-tall one
+		if self.hires && n == 0
+		{
+			self.draw_big_sprite(x, y);
+			return ;
+		}
+
+		let width = self.screen_width();
+		let height = self.screen_height();
+		// the original x and y coordinates wrap around, however if the sprite goes off screen, it is normally
+		// clipped instead of wrapped (quirks.sprite_wraps switches to wrapping the individual pixels instead)
+		let x_coord = self.v_reg[x as usize] as usize % width;
+		let y_coord = self.v_reg[y as usize] as usize % height;
 		self.v_reg[0xf as usize] = 0;
 		// if any pixels are flipped off, we need to write to the flag register
 		let mut flip = false;
@@ -427,22 +710,35 @@ impl Emu
 			// sprite is stored at addr specified by the I register
 			let row_addr = self.i_reg + row;
 			let pixels = self.ram[row_addr as usize];
-			for col in 0..8
+			let mut y = y_coord + row as usize;
+			if y >= height
 			{
-				if y_coord + row as usize >= SCREEN_HEIGHT
+				if self.quirks.sprite_wraps
+				{
+					y %= height;
+				}
+				else
 				{
 					break ;
 				}
+			}
+			for col in 0..8
+			{
 				if (pixels & (0b1000_0000 >> col)) != 0
 				{
-					
-					let x = x_coord + col as usize;
-					let y = y_coord + row as usize;
-					if x >= SCREEN_WIDTH
+					let mut x = x_coord + col as usize;
+					if x >= width
 					{
-						break ;
+						if self.quirks.sprite_wraps
+						{
+							x %= width;
+						}
+						else
+						{
+							break ;
+						}
 					}
-					let pixel_index = x + SCREEN_WIDTH * y;
+					let pixel_index = x + width * y;
 					// set flip to true if pixel was already on
 					flip |= self.screen[pixel_index];
 					// flip pixel
@@ -454,6 +750,64 @@ impl Emu
 		{
 			self.v_reg[0xf as usize] = 1;
 		}
+		self.draw_flag = true;
+	}
+
+	// SUPER-CHIP 16x16 sprite draw (DXY0 in hi-res mode); VF is set to the number of rows
+	// that had a collision, instead of a single 0/1 flag
+	fn draw_big_sprite(&mut self, x: u16, y: u16)
+	{
+		let width = self.screen_width();
+		let height = self.screen_height();
+		let x_coord = self.v_reg[x as usize] as usize % width;
+		let y_coord = self.v_reg[y as usize] as usize % height;
+		let mut collided_rows: u8 = 0;
+		for row in 0..16usize
+		{
+			// each row of a 16x16 sprite is 2 bytes, instead of the usual 1
+			let row_addr = self.i_reg + (row as u16) * 2;
+			let pixels = ((self.ram[row_addr as usize] as u16) << 8) | self.ram[(row_addr + 1) as usize] as u16;
+			let mut y = y_coord + row;
+			if y >= height
+			{
+				if self.quirks.sprite_wraps
+				{
+					y %= height;
+				}
+				else
+				{
+					break ;
+				}
+			}
+			let mut row_flip = false;
+			for col in 0..16usize
+			{
+				if (pixels & (0b1000_0000_0000_0000 >> col)) != 0
+				{
+					let mut x = x_coord + col;
+					if x >= width
+					{
+						if self.quirks.sprite_wraps
+						{
+							x %= width;
+						}
+						else
+						{
+							break ;
+						}
+					}
+					let pixel_index = x + width * y;
+					row_flip |= self.screen[pixel_index];
+					self.screen[pixel_index] ^= true;
+				}
+			}
+			if row_flip
+			{
+				collided_rows += 1;
+			}
+		}
+		self.v_reg[0xf as usize] = collided_rows;
+		self.draw_flag = true;
 	}
 
 	pub fn get_screen(&self) -> &[bool]
@@ -461,6 +815,120 @@ impl Emu
 		&self.screen
 	}
 
+	pub fn is_hires(&self) -> bool
+	{
+		self.hires
+	}
+
+	pub fn screen_width(&self) -> usize
+	{
+		if self.hires { HIRES_SCREEN_WIDTH } else { SCREEN_WIDTH }
+	}
+
+	pub fn screen_height(&self) -> usize
+	{
+		if self.hires { HIRES_SCREEN_HEIGHT } else { SCREEN_HEIGHT }
+	}
+
+	pub fn pc(&self) -> u16
+	{
+		self.pc
+	}
+
+	pub fn i_reg(&self) -> u16
+	{
+		self.i_reg
+	}
+
+	pub fn v_reg(&self) -> &[u8]
+	{
+		&self.v_reg
+	}
+
+	// decodes a raw opcode into a human-readable mnemonic, using the same nibble split as execute()
+	pub fn disassemble(op: u16) -> String
+	{
+		let digit1 = (op & 0xf000) >> 12;
+		let digit2 = (op & 0x0f00) >> 8;
+		let digit3 = (op & 0x00f0) >> 4;
+		let digit4 = op & 0x000f;
+		let nnn = op & 0x0fff;
+		let nn = op & 0x00ff;
+
+		match (digit1, digit2, digit3, digit4)
+		{
+			(0x0, 0x0, 0x0, 0x0) => "NOP".to_string(),
+			(0x0, 0x0, 0xe, 0x0) => "CLS".to_string(),
+			(0x0, 0x0, 0xe, 0xe) => "RET".to_string(),
+			(0x0, 0x0, 0xc, _) => format!("SCROLL DOWN {}", digit4),
+			(0x0, 0x0, 0xf, 0xb) => "SCROLL RIGHT".to_string(),
+			(0x0, 0x0, 0xf, 0xc) => "SCROLL LEFT".to_string(),
+			(0x0, 0x0, 0xf, 0xe) => "LORES".to_string(),
+			(0x0, 0x0, 0xf, 0xf) => "HIRES".to_string(),
+			(0x1, _, _, _) => format!("JMP {:#x}", nnn),
+			(0x2, _, _, _) => format!("CALL {:#x}", nnn),
+			(0x3, _, _, _) => format!("SKIP V{:x} == {:#x}", digit2, nn),
+			(0x4, _, _, _) => format!("SKIP V{:x} != {:#x}", digit2, nn),
+			(0x5, _, _, 0x0) => format!("SKIP V{:x} == V{:x}", digit2, digit3),
+			(0x9, _, _, 0x0) => format!("SKIP V{:x} != V{:x}", digit2, digit3),
+			(0x6, _, _, _) => format!("V{:x} = {:#x}", digit2, nn),
+			(0x7, _, _, _) => format!("V{:x} += {:#x}", digit2, nn),
+			(0x8, _, _, 0x0) => format!("V{:x} = V{:x}", digit2, digit3),
+			(0x8, _, _, 0x1) => format!("V{:x} |= V{:x}", digit2, digit3),
+			(0x8, _, _, 0x2) => format!("V{:x} &= V{:x}", digit2, digit3),
+			(0x8, _, _, 0x3) => format!("V{:x} ^= V{:x}", digit2, digit3),
+			(0x8, _, _, 0x4) => format!("V{:x} += V{:x}", digit2, digit3),
+			(0x8, _, _, 0x5) => format!("V{:x} -= V{:x}", digit2, digit3),
+			(0x8, _, _, 0x7) => format!("V{:x} = V{:x} - V{:x}", digit2, digit3, digit2),
+			(0x8, _, _, 0x6) => format!("V{:x} >>= 1", digit2),
+			(0x8, _, _, 0xe) => format!("V{:x} <<= 1", digit2),
+			(0xa, _, _, _) => format!("I = {:#x}", nnn),
+			(0xb, _, _, _) => format!("JMP {:#x} + V0", nnn),
+			(0xc, _, _, _) => format!("V{:x} = rand() & {:#x}", digit2, nn),
+			(0xd, _, _, _) => format!("DRAW V{:x} V{:x} {}", digit2, digit3, digit4),
+			(0xe, _, 0x9, 0xe) => format!("SKIP KEY V{:x}", digit2),
+			(0xe, _, 0xa, 0x1) => format!("SKIP NOT KEY V{:x}", digit2),
+			(0xf, _, 0x0, 0x7) => format!("V{:x} = DT", digit2),
+			(0xf, _, 0x1, 0x5) => format!("DT = V{:x}", digit2),
+			(0xf, _, 0x1, 0x8) => format!("ST = V{:x}", digit2),
+			(0xf, _, 0x1, 0xe) => format!("I += V{:x}", digit2),
+			(0xf, _, 0x0, 0xa) => format!("V{:x} = WAIT KEY", digit2),
+			(0xf, _, 0x2, 0x9) => format!("I = FONT V{:x}", digit2),
+			(0xf, _, 0x3, 0x0) => format!("I = BIGFONT V{:x}", digit2),
+			(0xf, _, 0x3, 0x3) => format!("BCD V{:x}", digit2),
+			(0xf, _, 0x5, 0x5) => format!("STORE V0..V{:x}", digit2),
+			(0xf, _, 0x6, 0x5) => format!("LOAD V0..V{:x}", digit2),
+			(0xf, _, 0x7, 0x5) => format!("FLAGS = V0..V{:x}", digit2),
+			(0xf, _, 0x8, 0x5) => format!("V0..V{:x} = FLAGS", digit2),
+			(_, _, _, _) => format!("DB {:#06x}", op)
+		}
+	}
+
+	// reads count instructions out of RAM starting at start, without executing them
+	pub fn disassemble_range(&self, start: u16, count: usize) -> Vec<(u16, u16, String)>
+	{
+		let mut out = Vec::with_capacity(count);
+		let mut addr = start;
+		for _ in 0..count
+		{
+			let op = ((self.ram[addr as usize] as u16) << 8) | self.ram[(addr + 1) as usize] as u16;
+			out.push((addr, op, Self::disassemble(op)));
+			addr += 2;
+		}
+		out
+	}
+
+	// true if the screen buffer has changed since the last clear_draw_flag() call
+	pub fn should_redraw(&self) -> bool
+	{
+		self.draw_flag
+	}
+
+	pub fn clear_draw_flag(&mut self)
+	{
+		self.draw_flag = false;
+	}
+
 	pub fn load(&mut self, data: &[u8])
 	{
 		let start = START_ADDR as usize;
@@ -472,4 +940,154 @@ impl Emu
 	{
 		self.keys[key] = down;
 	}
+
+	// serializes the full machine state into a compact, versioned byte buffer, suitable for
+	// save states or for diffing in tests
+	// note - the screen is prefixed with its own packed byte length, since it varies with hi-res mode
+	pub fn snapshot(&self) -> Vec<u8>
+	{
+		let packed_screen = pack_screen(&self.screen);
+
+		let mut out = Vec::with_capacity(4 + 2 + 2 + 2 + 1 + 1 + 1 + NUM_REGS + STACK_SIZE * 2 + NUM_FLAGS + RAM_SIZE + 2 + packed_screen.len());
+		out.extend_from_slice(&SNAPSHOT_MAGIC);
+		out.push(SNAPSHOT_VERSION);
+		out.extend_from_slice(&self.pc.to_le_bytes());
+		out.extend_from_slice(&self.i_reg.to_le_bytes());
+		out.extend_from_slice(&self.sp.to_le_bytes());
+		out.push(self.dt);
+		out.push(self.st);
+		out.push(self.hires as u8);
+		out.extend_from_slice(&self.v_reg);
+		for val in self.stack.iter()
+		{
+			out.extend_from_slice(&val.to_le_bytes());
+		}
+		out.extend_from_slice(&self.flags);
+		out.extend_from_slice(&self.ram);
+		out.extend_from_slice(&(packed_screen.len() as u16).to_le_bytes());
+		out.extend_from_slice(&packed_screen);
+
+		out
+	}
+
+	// repopulates every field from a buffer produced by snapshot(), after validating the header and length
+	pub fn restore(&mut self, data: &[u8]) -> Result<(), String>
+	{
+		if data.len() < 4
+		{
+			return Err("snapshot is too short to contain a header".to_string());
+		}
+		if data[0..3] != SNAPSHOT_MAGIC
+		{
+			return Err("snapshot is missing the CRISP-8 magic header".to_string());
+		}
+		if data[3] != SNAPSHOT_VERSION
+		{
+			return Err(format!("unsupported snapshot version: {}", data[3]));
+		}
+
+		let fixed_len = 4 + 2 + 2 + 2 + 1 + 1 + 1 + NUM_REGS + STACK_SIZE * 2 + NUM_FLAGS + RAM_SIZE + 2;
+		if data.len() < fixed_len
+		{
+			return Err(format!("snapshot is too short: expected at least {} bytes, got {}", fixed_len, data.len()));
+		}
+
+		let mut offset = 4;
+
+		let pc = u16::from_le_bytes([data[offset], data[offset + 1]]);
+		offset += 2;
+		let i_reg = u16::from_le_bytes([data[offset], data[offset + 1]]);
+		offset += 2;
+		let sp = u16::from_le_bytes([data[offset], data[offset + 1]]);
+		offset += 2;
+		let dt = data[offset];
+		offset += 1;
+		let st = data[offset];
+		offset += 1;
+		let hires = data[offset] != 0;
+		offset += 1;
+
+		let mut v_reg = [0u8; NUM_REGS];
+		v_reg.copy_from_slice(&data[offset..offset + NUM_REGS]);
+		offset += NUM_REGS;
+
+		let mut stack = [0u16; STACK_SIZE];
+		for slot in stack.iter_mut()
+		{
+			*slot = u16::from_le_bytes([data[offset], data[offset + 1]]);
+			offset += 2;
+		}
+
+		let mut flags = [0u8; NUM_FLAGS];
+		flags.copy_from_slice(&data[offset..offset + NUM_FLAGS]);
+		offset += NUM_FLAGS;
+
+		let mut ram = [0u8; RAM_SIZE];
+		ram.copy_from_slice(&data[offset..offset + RAM_SIZE]);
+		offset += RAM_SIZE;
+
+		let screen_bytes = u16::from_le_bytes([data[offset], data[offset + 1]]) as usize;
+		offset += 2;
+		if data.len() != offset + screen_bytes
+		{
+			return Err(format!("snapshot has wrong length: expected {}, got {}", offset + screen_bytes, data.len()));
+		}
+
+		// the screen's packed length must match what the restored hires flag implies, otherwise
+		// a corrupted or hand-edited snapshot would leave screen.len() != screen_width()*screen_height()
+		// and panic the next time draw()/draw_big_sprite() indexes it
+		let expected_screen_bytes = if hires
+		{
+			HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT / 8
+		}
+		else
+		{
+			SCREEN_WIDTH * SCREEN_HEIGHT / 8
+		};
+		if screen_bytes != expected_screen_bytes
+		{
+			return Err(format!("snapshot screen size doesn't match its resolution flag: expected {} bytes, got {}", expected_screen_bytes, screen_bytes));
+		}
+
+		let mut screen = vec![false; screen_bytes * 8];
+		unpack_screen(&data[offset..offset + screen_bytes], &mut screen);
+
+		self.pc = pc;
+		self.i_reg = i_reg;
+		self.sp = sp;
+		self.dt = dt;
+		self.st = st;
+		self.hires = hires;
+		self.v_reg = v_reg;
+		self.stack = stack;
+		self.flags = flags;
+		self.ram = ram;
+		self.screen = screen;
+		self.draw_flag = true;
+
+		Ok(())
+	}
+}
+
+// packs a bool-per-pixel screen buffer into one bit per pixel, MSB first
+fn pack_screen(screen: &[bool]) -> Vec<u8>
+{
+	let mut out = vec![0u8; screen.len() / 8];
+	for (i, pixel) in screen.iter().enumerate()
+	{
+		if *pixel
+		{
+			out[i / 8] |= 0b1000_0000 >> (i % 8);
+		}
+	}
+	out
+}
+
+// unpacks a bitfield produced by pack_screen() back into a bool-per-pixel buffer
+fn unpack_screen(packed: &[u8], screen: &mut [bool])
+{
+	for (i, pixel) in screen.iter_mut().enumerate()
+	{
+		*pixel = (packed[i / 8] & (0b1000_0000 >> (i % 8))) != 0;
+	}
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 extern crate sdl2;
 
 use crisp8_core::*;
+use sdl2::audio::{AudioCallback, AudioSpecDesired};
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
@@ -12,22 +13,70 @@ use std::fs::File;
 use std::io::Read;
 
 const SCALE: u32 = 15;
-const WINDOW_WIDTH: u32 = (SCREEN_WIDTH as u32) * SCALE;
-const WINDOW_HEIGHT: u32 = (SCREEN_HEIGHT as u32) * SCALE;
+// sized for the hi-res (SUPER-CHIP) resolution; since it's exactly double the lo-res one in
+// each dimension, the lo-res pixel scale below still divides evenly instead of leaving a
+// letterboxed strip around the picture
+const WINDOW_WIDTH: u32 = (HIRES_SCREEN_WIDTH as u32) * SCALE;
+const WINDOW_HEIGHT: u32 = (HIRES_SCREEN_HEIGHT as u32) * SCALE;
 const TICKS_PER_FRAME: usize = 10;
+const BEEP_FREQ: f32 = 440.0;
+const BEEP_VOLUME: f32 = 0.25;
+
+// toggles a fixed-amplitude square wave on and off to produce the CHIP-8 beep
+struct SquareWave
+{
+	samples_per_half_period: f32,
+	sample_count: f32,
+	volume: f32
+}
+
+impl AudioCallback for SquareWave
+{
+	type Channel = f32;
+
+	fn callback(&mut self, out: &mut [f32])
+	{
+		for sample in out.iter_mut()
+		{
+			let sign = if (self.sample_count / self.samples_per_half_period) as u32 % 2 == 0 { 1.0 } else { -1.0 };
+			*sample = sign * self.volume;
+			self.sample_count += 1.0;
+		}
+	}
+}
 
 fn main()
 {
 	let args: Vec<_> = env::args().collect();
-	if args.len() != 2
+	let (rom_path, quirks) = match parse_args(&args)
 	{
-		println!("Usage: cargo run rom_path_here");
-		return ;
-	}
+		Some(parsed) => parsed,
+		None => {
+			println!("Usage: cargo run -- [--quirks cosmac-vip|chip48|modern] rom_path_here");
+			return ;
+		}
+	};
 
 	// SDL setup
 	let sdl = sdl2::init().unwrap();
 	let video_subsystem = sdl.video().unwrap();
+	let audio_subsystem = sdl.audio().unwrap();
+
+	let audio_spec = AudioSpecDesired {
+		freq: Some(44100),
+		channels: Some(1),
+		samples: None
+	};
+	let audio_device = audio_subsystem
+		.open_playback(None, &audio_spec, |spec| {
+			SquareWave {
+				samples_per_half_period: spec.freq as f32 / (2.0 * BEEP_FREQ),
+				sample_count: 0.0,
+				volume: BEEP_VOLUME
+			}
+		})
+		.unwrap();
+
 	let window = video_subsystem
 		.window("CRISP-8", WINDOW_WIDTH, WINDOW_HEIGHT)
 		.position_centered()
@@ -46,19 +95,47 @@ fn main()
 	let mut event_pump = sdl.event_pump().unwrap();
 
 	// Interpreter setup + loading program
-	let mut crisp8 = Emu::new();
+	let mut crisp8 = Emu::new_with_quirks(quirks);
 
-	let mut rom = File::open(&args[1]).expect("Unable to open file");
+	let mut rom = File::open(&rom_path).expect("Unable to open file");
 	let mut buff = Vec::new();
 	rom.read_to_end(&mut buff).unwrap();
 	crisp8.load(&buff);
+
+	// the single save-state slot used by the F5 (save) / F9 (restore) shortcuts
+	let mut save_slot: Option<Vec<u8>> = None;
+
+	// debug mode: P toggles pause, N steps one instruction while paused, printing it and the register state
+	let mut paused = false;
+
 	'main: loop
 	{
+		let mut single_step = false;
+
 		for event in event_pump.poll_iter()
 		{
 			match event
 			{
 				Event::Quit { .. } | Event::KeyDown {keycode: Some(Keycode::Escape), .. } => break 'main,
+				Event::KeyDown { keycode: Some(Keycode::F5), .. } => {
+					save_slot = Some(crisp8.snapshot());
+				},
+				Event::KeyDown { keycode: Some(Keycode::F9), .. } => {
+					if let Some(data) = &save_slot
+					{
+						if let Err(e) = crisp8.restore(data)
+						{
+							println!("Failed to restore snapshot: {}", e);
+						}
+					}
+				},
+				Event::KeyDown { keycode: Some(Keycode::P), .. } => {
+					paused = !paused;
+					println!("{}", if paused { "-- paused --" } else { "-- resumed --" });
+				},
+				Event::KeyDown { keycode: Some(Keycode::N), .. } => {
+					single_step = true;
+				},
 				Event::KeyDown { keycode: Some(key), .. } => {
 					if let Some(k) = convert_key(key)
 					{
@@ -75,37 +152,113 @@ fn main()
 			}
 		}
 
-		for _ in 0..TICKS_PER_FRAME
+		if paused
+		{
+			if single_step
+			{
+				print_debug_state(&crisp8);
+				crisp8.step();
+			}
+		}
+		else
+		{
+			for _ in 0..TICKS_PER_FRAME
+			{
+				crisp8.tick();
+			}
+			crisp8.tick_timers();
+		}
+
+		if crisp8.is_sound_active()
+		{
+			audio_device.resume();
+		}
+		else
+		{
+			audio_device.pause();
+		}
+
+		if crisp8.should_redraw()
 		{
-			crisp8.tick();
+			draw_screen(&crisp8, &mut canvas);
+			crisp8.clear_draw_flag();
 		}
-		crisp8.tick_timers();
-		draw_screen(&crisp8, &mut canvas);
 	}
 }
 
+// prints the instruction about to execute and the current register state, for single-step debugging
+fn print_debug_state(emu: &Emu)
+{
+	let pc = emu.pc();
+	let decoded = emu.disassemble_range(pc, 1);
+	let (_, op, mnemonic) = &decoded[0];
+	println!("{:#06x}: {:#06x}  {}", pc, op, mnemonic);
+	println!("  I = {:#06x}  V = {:02x?}", emu.i_reg(), emu.v_reg());
+}
+
 fn draw_screen(emu: &Emu, canvas: &mut Canvas<Window>)
 {
 	canvas.set_draw_color(Color::RGB(0, 0, 0));
 	canvas.clear();
 
+	// the window stays a fixed size, so the pixel scale shrinks to fit whichever resolution is active
+	let width = emu.screen_width();
+	let height = emu.screen_height();
+	let scale_x = WINDOW_WIDTH / width as u32;
+	let scale_y = WINDOW_HEIGHT / height as u32;
+
 	let screen_buff = emu.get_screen();
-	
+
 	canvas.set_draw_color(Color::RGB(255, 255, 255));
 	for (index, pixel) in screen_buff.iter().enumerate()
 	{
 		if *pixel
 		{
-			let x = (index % SCREEN_WIDTH) as u32;
-			let y = (index / SCREEN_WIDTH) as u32;
-	
-			let rect = Rect::new((x * SCALE) as i32, (y * SCALE) as i32, SCALE, SCALE);
+			let x = (index % width) as u32;
+			let y = (index / width) as u32;
+
+			let rect = Rect::new((x * scale_x) as i32, (y * scale_y) as i32, scale_x, scale_y);
 			canvas.fill_rect(rect).unwrap();
 		}
 	}
 	canvas.present();
 }
 
+// pulls the ROM path and an optional "--quirks <preset>" flag out of the command line,
+// so a ROM that targets a specific interpreter can be launched with the matching compatibility behaviors
+fn parse_args(args: &[String]) -> Option<(String, Quirks)>
+{
+	let mut rom_path = None;
+	let mut quirks = Quirks::default();
+
+	let mut i = 1;
+	while i < args.len()
+	{
+		if args[i] == "--quirks"
+		{
+			let preset = args.get(i + 1)?;
+			quirks = match preset.as_str()
+			{
+				"cosmac-vip" => Quirks::cosmac_vip(),
+				"chip48" => Quirks::chip48(),
+				"modern" => Quirks::modern(),
+				_ => {
+					println!("Unknown quirks preset '{}' (expected cosmac-vip, chip48, or modern)", preset);
+					return None;
+				}
+			};
+			i += 2;
+		}
+		else
+		{
+			rom_path = Some(args[i].clone());
+			i += 1;
+		}
+	}
+
+	rom_path.map(|path| (path, quirks))
+}
+
 fn convert_key(key: Keycode) -> Option<usize>
 {
 	match key